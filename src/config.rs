@@ -0,0 +1,163 @@
+//! Loads defaults and per-interface profiles from `rotate-mac-address.toml`
+//! in the platform config directory, so a long-running rotation daemon can
+//! be configured without editing unit files or command lines.
+//!
+//! Precedence is CLI flags, then this file, then the compiled-in defaults
+//! in `main`.
+
+use {
+    crate::{Vendor, NAME},
+    anyhow::{Context, Error},
+    directories::ProjectDirs,
+    serde::Deserialize,
+    std::{collections::HashMap, fs, io::ErrorKind, path::PathBuf},
+};
+
+pub(crate) const FILE_NAME: &str = "rotate-mac-address.toml";
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct Config {
+    pub(crate) cycle_seconds: Option<usize>,
+    pub(crate) name_words: Option<usize>,
+    pub(crate) use_command: Option<bool>,
+
+    #[serde(default)]
+    pub(crate) profile: Vec<Profile>,
+
+    /// A hosts-file-style lookup from interface name to a friendly label
+    /// used in logs, e.g. `eth0 = "office-uplink"`.
+    #[serde(default)]
+    pub(crate) labels: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Profile {
+    pub(crate) interface: String,
+    pub(crate) cycle_seconds: Option<usize>,
+    pub(crate) variance: Option<f64>,
+    pub(crate) vendors: Option<Vec<String>>,
+    pub(crate) nickname: Option<String>,
+}
+
+impl Config {
+    pub(crate) fn profile_for(&self, interface_name: &str) -> Option<&Profile> {
+        self.profile
+            .iter()
+            .find(|profile| profile.interface == interface_name)
+    }
+
+    pub(crate) fn label_for<'s>(&'s self, interface_name: &'s str) -> &'s str {
+        self.labels
+            .get(interface_name)
+            .map(String::as_str)
+            .unwrap_or(interface_name)
+    }
+}
+
+impl Profile {
+    /// Resolves this profile's `vendors` entries against [`Vendor`]'s
+    /// `Display` names, ignoring (and warning about) any that don't match.
+    ///
+    /// A list that resolves to no recognized vendors at all (an explicit
+    /// `vendors = []`, or entries that are all typos) falls back to `None`
+    /// rather than handing [`crate::VendorPicker::pick`] an empty slice to
+    /// choose from.
+    pub(crate) fn allowed_vendors(&self) -> Option<Vec<Vendor>> {
+        let vendors = self.vendors.as_ref()?;
+        let resolved: Vec<Vendor> = vendors
+            .iter()
+            .filter_map(|name| {
+                let vendor = Vendor::from_display_name(name);
+                if vendor.is_none() {
+                    eprintln!("ignoring unrecognized vendor `{name}` in config file");
+                }
+                vendor
+            })
+            .collect();
+
+        if resolved.is_empty() {
+            eprintln!(
+                "no recognized vendors left in `vendors` for interface `{}`; falling back to all vendors",
+                self.interface
+            );
+            None
+        } else {
+            Some(resolved)
+        }
+    }
+}
+
+fn path() -> Option<PathBuf> {
+    ProjectDirs::from("", "", NAME).map(|dirs| dirs.config_dir().join(FILE_NAME))
+}
+
+pub(crate) fn load() -> Result<Config, Error> {
+    let Some(path) = path() else {
+        return Ok(Config::default());
+    };
+
+    match fs::read_to_string(&path) {
+        Ok(contents) => toml::from_str(&contents)
+            .with_context(|| format!("failed to parse config file at {}", path.display())),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(Config::default()),
+        Err(err) => {
+            Err(err).with_context(|| format!("failed to read config file at {}", path.display()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(interface: &str) -> Profile {
+        Profile {
+            interface: interface.to_owned(),
+            cycle_seconds: None,
+            variance: None,
+            vendors: None,
+            nickname: None,
+        }
+    }
+
+    #[test]
+    fn profile_for_matches_by_interface_name() {
+        let config = Config {
+            profile: vec![profile("eth0"), profile("wlan0")],
+            ..Config::default()
+        };
+
+        assert_eq!(config.profile_for("wlan0").unwrap().interface, "wlan0");
+        assert!(config.profile_for("eth1").is_none());
+    }
+
+    #[test]
+    fn label_for_falls_back_to_the_interface_name() {
+        let mut config = Config::default();
+        config
+            .labels
+            .insert("eth0".to_owned(), "office-uplink".to_owned());
+
+        assert_eq!(config.label_for("eth0"), "office-uplink");
+        assert_eq!(config.label_for("wlan0"), "wlan0");
+    }
+
+    #[test]
+    fn allowed_vendors_drops_unrecognized_entries() {
+        let mut profile = profile("eth0");
+        profile.vendors = Some(vec!["Intel".to_owned(), "bogus".to_owned()]);
+
+        let allowed = profile.allowed_vendors().unwrap();
+        assert_eq!(allowed.len(), 1);
+    }
+
+    #[test]
+    fn allowed_vendors_falls_back_to_none_when_nothing_resolves() {
+        let mut profile = profile("eth0");
+        profile.vendors = Some(vec!["bogus".to_owned()]);
+        assert_eq!(profile.allowed_vendors(), None);
+
+        profile.vendors = Some(vec![]);
+        assert_eq!(profile.allowed_vendors(), None);
+    }
+}
@@ -0,0 +1,168 @@
+//! Enumerates the host's network interfaces via `getifaddrs(3)`, which is
+//! available on both Linux and macOS/BSD, so defaulting and validating
+//! `--interface-name` doesn't have to guess at a platform-specific name
+//! like `eth0` that may not even exist.
+
+use {
+    anyhow::{anyhow, Context, Error},
+    nix::{
+        ifaddrs::{getifaddrs, InterfaceAddress},
+        net::if_::InterfaceFlags,
+        sys::socket::{AddressFamily, SockaddrLike},
+    },
+    std::collections::HashMap,
+};
+
+#[derive(Debug, Clone)]
+pub(crate) struct Interface {
+    pub(crate) name: String,
+    pub(crate) up: bool,
+    pub(crate) loopback: bool,
+    pub(crate) has_link_layer_address: bool,
+}
+
+#[cfg(target_os = "linux")]
+fn is_link_layer_address(addr: &InterfaceAddress) -> bool {
+    addr.address.as_ref().and_then(SockaddrLike::family) == Some(AddressFamily::Packet)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_link_layer_address(addr: &InterfaceAddress) -> bool {
+    addr.address.as_ref().and_then(SockaddrLike::family) == Some(AddressFamily::Link)
+}
+
+/// The well-known naming conventions used by virtual interfaces (bridges,
+/// tunnels, taps, and veth endpoints) on Linux and macOS/BSD, so
+/// `--all-physical` doesn't sweep up `docker0`, `veth1234`, or similar.
+///
+/// Neither platform offers a cheap, portable way to ask "is this NIC
+/// backed by real hardware?" short of walking sysfs (Linux-only) or IOKit
+/// (macOS-only), so this crate settles for the same prefix list every
+/// other rotate-mac-address-style tool uses in practice.
+const VIRTUAL_INTERFACE_PREFIXES: &[&str] = &[
+    "docker", "veth", "br-", "virbr", "bridge", "tun", "tap", "wg", "utun", "ipsec", "gif", "stf",
+    "vmnet", "vboxnet",
+];
+
+fn is_virtual(name: &str) -> bool {
+    VIRTUAL_INTERFACE_PREFIXES
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+}
+
+/// Lists every interface the host knows about, deduplicating the multiple
+/// `getifaddrs` entries (one per address family) each interface produces.
+pub(crate) fn enumerate() -> Result<Vec<Interface>, Error> {
+    let mut by_name: HashMap<String, Interface> = HashMap::new();
+
+    for addr in getifaddrs().context("failed to enumerate network interfaces")? {
+        let entry = by_name
+            .entry(addr.interface_name.clone())
+            .or_insert_with(|| Interface {
+                name: addr.interface_name.clone(),
+                up: addr.flags.contains(InterfaceFlags::IFF_UP),
+                loopback: addr.flags.contains(InterfaceFlags::IFF_LOOPBACK),
+                has_link_layer_address: false,
+            });
+
+        if is_link_layer_address(&addr) {
+            entry.has_link_layer_address = true;
+        }
+    }
+
+    let mut interfaces: Vec<_> = by_name.into_values().collect();
+    interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(interfaces)
+}
+
+fn available_names(interfaces: &[Interface]) -> String {
+    interfaces
+        .iter()
+        .map(|interface| interface.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The first up, non-loopback interface with a link-layer address, used as
+/// the default when `--interface-name` is omitted and no config-file
+/// profile names any interfaces either.
+pub(crate) fn default_interface(interfaces: &[Interface]) -> Option<&Interface> {
+    interfaces
+        .iter()
+        .find(|interface| interface.up && !interface.loopback && interface.has_link_layer_address)
+}
+
+/// Every non-virtual, non-loopback interface with a link-layer address, for
+/// `--all-physical`.
+pub(crate) fn physical(interfaces: &[Interface]) -> Vec<String> {
+    interfaces
+        .iter()
+        .filter(|interface| {
+            !interface.loopback
+                && interface.has_link_layer_address
+                && !is_virtual(&interface.name)
+        })
+        .map(|interface| interface.name.clone())
+        .collect()
+}
+
+/// Confirms `name` is one of the host's real interfaces, producing a clear
+/// error listing what is actually available rather than letting the
+/// downstream ioctl/command fail opaquely.
+pub(crate) fn validate(name: &str, interfaces: &[Interface]) -> Result<(), Error> {
+    if interfaces.iter().any(|interface| interface.name == name) {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "no such interface `{name}`; available interfaces: {}",
+            available_names(interfaces)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interface(name: &str, up: bool, loopback: bool, has_link_layer_address: bool) -> Interface {
+        Interface {
+            name: name.to_owned(),
+            up,
+            loopback,
+            has_link_layer_address,
+        }
+    }
+
+    #[test]
+    fn is_virtual_recognizes_common_software_interfaces() {
+        for name in ["docker0", "veth1234abc", "br-deadbeef0123", "tap0"] {
+            assert!(is_virtual(name), "expected `{name}` to be virtual");
+        }
+        assert!(!is_virtual("eth0"));
+        assert!(!is_virtual("en0"));
+    }
+
+    #[test]
+    fn default_interface_skips_down_loopback_and_addressless_interfaces() {
+        let interfaces = [
+            interface("lo", true, true, true),
+            interface("eth1", false, false, true),
+            interface("eth2", true, false, false),
+            interface("eth0", true, false, true),
+        ];
+
+        assert_eq!(default_interface(&interfaces).unwrap().name, "eth0");
+    }
+
+    #[test]
+    fn physical_excludes_loopback_addressless_and_virtual_interfaces() {
+        let interfaces = [
+            interface("lo", true, true, true),
+            interface("eth0", true, false, true),
+            interface("eth1", true, false, false),
+            interface("docker0", true, false, true),
+        ];
+
+        assert_eq!(physical(&interfaces), vec!["eth0".to_owned()]);
+    }
+}
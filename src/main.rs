@@ -1,4 +1,19 @@
-#![forbid(unsafe_code)]
+// Deliberate, reviewed exception, not an oversight: direct-ioctl MAC
+// address changes (see `netlink`) need `unsafe` that `nix` cannot wrap
+// safely on its behalf, and shelling out to `ip`/`ifconfig` instead (the
+// only way to keep this a true `forbid`) is the exact subprocess dependency
+// that feature was meant to remove. `forbid` cannot be locally overridden by
+// an inner `allow`, so the lint is downgraded crate-wide to `deny` here;
+// `netlink` remains the only module permitted to `#[allow(unsafe_code)]`,
+// and its `unsafe` is confined to building `ifreq` structs and issuing the
+// ioctls.
+#![deny(unsafe_code)]
+
+mod config;
+mod interfaces;
+mod mnemonic;
+mod netlink;
+mod scheduler;
 
 use {
     anyhow::{anyhow, Error},
@@ -7,23 +22,21 @@ use {
     std::{
         fmt::{self, Display, Formatter},
         process::Command,
-        thread::sleep,
-        time::Duration,
     },
 };
 
-const NAME: &str = "rotate-mac-address";
+pub(crate) const NAME: &str = "rotate-mac-address";
 const AUTHOR: &str = "Louis Jackman";
 const ABOUT: &str = "Rotate MAC addresses on a specified interval, with a bit of variation \
     added. Requires superuser privileges. Supports macOS and Linux.";
 
-const DEFAULT_DEVICE_NAME: &str = "eth0";
 const DEFAULT_CYCLE_SECONDS: usize = 30 * 60;
+const DEFAULT_NAME_WORDS: usize = 3;
 
-const CYCLE_VARIANCE: f64 = 0.25;
-const MAX_AMOUNT_OF_ERRORS: usize = 3;
+pub(crate) const CYCLE_VARIANCE: f64 = 0.25;
+pub(crate) const MAX_AMOUNT_OF_ERRORS: usize = 3;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 enum Vendor {
     Intel,
     HewlettPackard,
@@ -33,13 +46,13 @@ enum Vendor {
 }
 
 impl Vendor {
-    fn mac_address_prefix(self) -> &'static str {
+    fn mac_address_prefix(self) -> [u8; 3] {
         match self {
-            Self::Intel => "00:1b:77",
-            Self::HewlettPackard => "00:1b:78",
-            Self::Foxconn => "00:01:6c",
-            Self::Cisco => "00:10:29",
-            Self::Amd => "00:0c:87",
+            Self::Intel => [0x00, 0x1b, 0x77],
+            Self::HewlettPackard => [0x00, 0x1b, 0x78],
+            Self::Foxconn => [0x00, 0x01, 0x6c],
+            Self::Cisco => [0x00, 0x10, 0x29],
+            Self::Amd => [0x00, 0x0c, 0x87],
         }
     }
 }
@@ -57,21 +70,37 @@ impl Display for Vendor {
     }
 }
 
+impl Vendor {
+    /// Parses one of this enum's `Display` names back out, case-insensitively,
+    /// for use by config-file vendor allow-lists.
+    pub(crate) fn from_display_name(name: &str) -> Option<Self> {
+        VENDORS
+            .iter()
+            .copied()
+            .find(|vendor| vendor.to_string().eq_ignore_ascii_case(name))
+    }
+}
+
 static VENDORS: [Vendor; 5] = {
     use Vendor::*;
     [Intel, HewlettPackard, Foxconn, Cisco, Amd]
 };
 
-struct VendorPicker<'r>(&'r mut ThreadRng);
+pub(crate) struct VendorPicker<'r>(&'r mut ThreadRng);
 
 impl<'r> VendorPicker<'r> {
-    fn new(rng: &'r mut ThreadRng) -> Self {
+    pub(crate) fn new(rng: &'r mut ThreadRng) -> Self {
         Self(rng)
     }
 
-    fn pick(&mut self) -> Vendor {
+    /// Picks a random vendor, restricted to `allowed` when given (e.g. from
+    /// a config-file profile's vendor allow-list), or any vendor otherwise.
+    pub(crate) fn pick(&mut self, allowed: Option<&[Vendor]>) -> Vendor {
         let Self(rng) = self;
-        *VENDORS.choose(rng).expect("`VENDORS` cannot be empty")
+        let candidates = allowed.unwrap_or(&VENDORS);
+        *candidates
+            .choose(rng)
+            .expect("vendor candidates cannot be empty")
     }
 }
 
@@ -111,55 +140,91 @@ fn new_set_mac_address<'a>(spec: SetMacAddressSpec<'a>) -> (Command, [&'a str; 6
     (cmd, args)
 }
 
-fn random_digit(rng: &mut ThreadRng) -> u8 {
-    rng.gen_range(1..=9)
+fn random_byte(rng: &mut ThreadRng) -> u8 {
+    rng.gen_range(0..=u8::MAX)
+}
+
+/// Bit 0 (the multicast bit) of a MAC address's first octet: every address
+/// this tool emits must be unicast, so it is always cleared.
+const MULTICAST_BIT: u8 = 0b0000_0001;
+
+/// Bit 1 (the locally-administered/U-L bit) of the first octet: set on
+/// `--locally-administered` addresses so they can't collide with a real OUI.
+const LOCALLY_ADMINISTERED_BIT: u8 = 0b0000_0010;
+
+fn format_mac_address(bytes: &[u8; 6]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<Vec<_>>()
+        .join(":")
 }
 
 fn new_random_mac_address<'r>(
     rng: &mut ThreadRng,
     picker: &mut VendorPicker<'r>,
-) -> (Vendor, String) {
-    let vendor = picker.pick();
-
-    let new_address = {
-        const LENGTH: u8 = 3;
-        (1..=LENGTH).fold(
-            {
-                let mut addr = vendor.mac_address_prefix().to_owned();
-                addr.push(':');
-                addr
-            },
-            |mut addr, i| {
-                let (first, second) = (random_digit(rng), random_digit(rng));
-                addr.push_str(&first.to_string());
-                addr.push_str(&second.to_string());
-                if i < LENGTH {
-                    addr.push(':');
-                }
-                addr
-            },
+    allowed_vendors: Option<&[Vendor]>,
+    name_words: usize,
+    fixed_nickname: Option<&str>,
+    locally_administered: bool,
+) -> (Option<Vendor>, String, Option<String>) {
+    let (vendor, mut bytes) = if locally_administered {
+        let mut bytes = [0u8; 6];
+        bytes.fill_with(|| random_byte(rng));
+        (None, bytes)
+    } else {
+        let vendor = picker.pick(allowed_vendors);
+        let [first, second, third] = vendor.mac_address_prefix();
+        (
+            Some(vendor),
+            [
+                first,
+                second,
+                third,
+                random_byte(rng),
+                random_byte(rng),
+                random_byte(rng),
+            ],
         )
     };
 
-    (vendor, new_address)
+    bytes[0] &= !MULTICAST_BIT;
+    if locally_administered {
+        bytes[0] |= LOCALLY_ADMINISTERED_BIT;
+    }
+
+    let new_address = format_mac_address(&bytes);
+
+    let name = fixed_nickname
+        .map(str::to_owned)
+        .or_else(|| (0 < name_words).then(|| mnemonic::name_for(&bytes, name_words)));
+
+    (vendor, new_address, name)
 }
 
-fn variate(rng: &mut ThreadRng, seconds: usize, variance: f64) -> f64 {
+pub(crate) fn variate(rng: &mut ThreadRng, seconds: usize, variance: f64) -> f64 {
     let base: f64 = rng.gen_range(0.0..=1.0);
     let delta = (base - 0.5) * variance;
     (seconds as f64) + ((seconds as f64) * delta)
 }
 
-fn set_mac_address(
-    rng: &mut ThreadRng,
-    picker: &mut VendorPicker,
+fn parse_mac_address_bytes(mac_address: &str) -> Result<[u8; 6], Error> {
+    let mut bytes = [0u8; 6];
+    for (dest, octet) in bytes.iter_mut().zip(mac_address.split(':')) {
+        *dest = u8::from_str_radix(octet, 16)
+            .map_err(|_| anyhow!("`{mac_address}` is not a colon-separated hex MAC address"))?;
+    }
+    Ok(bytes)
+}
+
+fn set_mac_address_via_command(
     interface_name: &str,
+    new_address: &str,
     dry_run: bool,
-) -> Result<(String, Vendor), Error> {
-    let (vendor, new_address) = new_random_mac_address(rng, picker);
+) -> Result<(), Error> {
     let (mut cmd, args) = new_set_mac_address(SetMacAddressSpec {
         interface_name,
-        new_mac_address: &new_address,
+        new_mac_address: new_address,
     });
 
     if dry_run {
@@ -178,10 +243,63 @@ fn set_mac_address(
     } else {
         cmd.output()?;
     };
-    Ok((new_address, vendor))
+    Ok(())
+}
+
+fn set_mac_address_via_netlink(
+    interface_name: &str,
+    new_address: &str,
+    dry_run: bool,
+) -> Result<(), Error> {
+    let bytes = parse_mac_address_bytes(new_address)?;
+
+    if dry_run {
+        println!(
+            "Dry-running enabled; would otherwise issue the hardware-address-change \
+            ioctl(s) on interface {interface_name} to set it to {new_address}"
+        );
+        Ok(())
+    } else {
+        netlink::set_mac_address(interface_name, &bytes)
+    }
+}
+
+/// Per-interface settings gathered from CLI flags and config-file profiles,
+/// layered by `main` before a rotation is attempted.
+pub(crate) struct RotationSpec<'s> {
+    pub(crate) allowed_vendors: Option<&'s [Vendor]>,
+    pub(crate) name_words: usize,
+    pub(crate) fixed_nickname: Option<&'s str>,
+    pub(crate) use_command: bool,
+    pub(crate) dry_run: bool,
+    pub(crate) locally_administered: bool,
+}
+
+pub(crate) fn set_mac_address(
+    rng: &mut ThreadRng,
+    picker: &mut VendorPicker,
+    interface_name: &str,
+    spec: &RotationSpec,
+) -> Result<(String, Option<Vendor>, Option<String>), Error> {
+    let (vendor, new_address, name) = new_random_mac_address(
+        rng,
+        picker,
+        spec.allowed_vendors,
+        spec.name_words,
+        spec.fixed_nickname,
+        spec.locally_administered,
+    );
+
+    if spec.use_command {
+        set_mac_address_via_command(interface_name, &new_address, spec.dry_run)?;
+    } else {
+        set_mac_address_via_netlink(interface_name, &new_address, spec.dry_run)?;
+    }
+
+    Ok((new_address, vendor, name))
 }
 
-fn collate_errors(errors: Vec<Error>) -> Error {
+pub(crate) fn collate_errors(errors: Vec<Error>) -> Error {
     let length = errors.len();
     let message = errors.iter().map(|e| e.to_string()).zip(1..).fold(
         String::new(),
@@ -196,48 +314,48 @@ fn collate_errors(errors: Vec<Error>) -> Error {
     anyhow!("Failures: {message}")
 }
 
-fn rotate_mac_addresses(
+/// Runs a single interface's error-budgeted rotation once its timer has
+/// fired, reusing the same per-interface accumulator across calls. `label`
+/// is what gets printed in logs (the config file's `labels` entry for the
+/// interface, or `interface_name` itself by default); `interface_name` is
+/// always what the kernel is told to change.
+pub(crate) fn rotate_once(
     rng: &mut ThreadRng,
     picker: &mut VendorPicker,
     interface_name: &str,
-    cycle_seconds: usize,
-    dry_run: bool,
+    label: &str,
+    spec: &RotationSpec,
+    errors: &mut Vec<Error>,
 ) -> Result<(), Error> {
-    let mut errors: Vec<Error> = vec![];
-    errors.reserve(MAX_AMOUNT_OF_ERRORS);
-
-    loop {
-        let change_result = set_mac_address(rng, picker, interface_name, dry_run);
-
-        match change_result {
-            Ok((new_address, vendor)) => {
-                errors.clear();
-                println!(
-                    "Successfully changed MAC address on interface \
-                    {interface_name} to {new_address} of vendor {vendor}"
-                );
-            }
-            Err(err) => {
-                errors.push(err);
-                let errors_count = MAX_AMOUNT_OF_ERRORS - errors.len();
-                eprintln!(
-                    "Failed to change MAC address on interface \
-                        {interface_name}. Only {errors_count} sequential \
-                        errors left until the program aborts."
-                );
-                if MAX_AMOUNT_OF_ERRORS <= errors.len() {
-                    break Err(collate_errors(errors));
-                }
+    let change_result = set_mac_address(rng, picker, interface_name, spec);
+
+    match change_result {
+        Ok((new_address, vendor, name)) => {
+            errors.clear();
+            let named = name.map(|name| format!(" ({name})")).unwrap_or_default();
+            let vendor_suffix = vendor
+                .map(|vendor| format!(" of vendor {vendor}"))
+                .unwrap_or_else(|| " (locally administered)".to_owned());
+            println!(
+                "Successfully changed MAC address on interface \
+                {label} to {new_address}{named}{vendor_suffix}"
+            );
+            Ok(())
+        }
+        Err(err) => {
+            errors.push(err);
+            let errors_count = MAX_AMOUNT_OF_ERRORS - errors.len();
+            eprintln!(
+                "Failed to change MAC address on interface \
+                    {label}. Only {errors_count} sequential \
+                    errors left until this interface is abandoned."
+            );
+            if MAX_AMOUNT_OF_ERRORS <= errors.len() {
+                Err(collate_errors(std::mem::take(errors)))
+            } else {
+                Ok(())
             }
         }
-
-        let variation = variate(rng, cycle_seconds, CYCLE_VARIANCE);
-        let duration = Duration::from_millis((variation.round() * 1000.0) as u64);
-        println!(
-            "waiting for {} seconds until the next rotation",
-            duration.as_secs(),
-        );
-        sleep(duration);
     }
 }
 
@@ -246,25 +364,186 @@ fn rotate_mac_addresses(
 #[command(author = AUTHOR)]
 #[command(about = ABOUT)]
 struct Flags {
-    #[arg(long, default_value_t = DEFAULT_DEVICE_NAME.to_owned())]
-    interface_name: String,
-    #[arg(long, default_value_t = DEFAULT_CYCLE_SECONDS)]
-    cycle_seconds: usize,
+    /// The interface to rotate. Can be passed multiple times, or as a
+    /// comma-separated list, to rotate several interfaces concurrently,
+    /// each on its own independent cycle. Defaults to every interface
+    /// named in the config file's `[[profile]]` tables, falling back to
+    /// the first up, non-loopback interface with a link-layer address if
+    /// there are none. Validated against the host's real interfaces.
+    #[arg(long, value_delimiter = ',')]
+    interface_name: Option<Vec<String>>,
+    /// Rotate every physical (non-virtual, non-loopback) interface found on
+    /// the host, instead of relying on `--interface-name` or the config file.
+    #[arg(long, default_value_t = false)]
+    all_physical: bool,
+    #[arg(long)]
+    cycle_seconds: Option<usize>,
+    /// Fall back to shelling out to `ip`/`ifconfig` instead of issuing the
+    /// hardware-address ioctls directly.
+    #[arg(long, default_value_t = false)]
+    use_command: bool,
+    /// How many words long the mnemonic name for each rotated address should be.
+    #[arg(long)]
+    name_words: Option<usize>,
+    /// Don't print a mnemonic name alongside each rotated address.
+    #[arg(long, default_value_t = false)]
+    no_names: bool,
+    /// Ignore the vendor-prefix table and instead generate a valid unicast,
+    /// locally-administered address, which is what most randomization tools
+    /// emit to avoid impersonating a registered OUI.
+    #[arg(long, default_value_t = false)]
+    locally_administered: bool,
     #[arg(long, default_value_t = false)]
     dry_run: bool,
 }
 
 fn main() -> Result<(), Error> {
     let flags = Flags::parse();
+    let config = config::load()?;
     let mut rng = thread_rng();
     let mut vendor_picker_rng = thread_rng();
     let mut vendor_picker = VendorPicker::new(&mut vendor_picker_rng);
 
-    rotate_mac_addresses(
+    let name_words = if flags.no_names {
+        0
+    } else {
+        flags
+            .name_words
+            .or(config.name_words)
+            .unwrap_or(DEFAULT_NAME_WORDS)
+    };
+    let use_command = flags.use_command || config.use_command.unwrap_or(false);
+
+    let discovered = interfaces::enumerate()?;
+
+    let interface_names = if flags.all_physical {
+        let physical = interfaces::physical(&discovered);
+        if physical.is_empty() {
+            return Err(anyhow!(
+                "--all-physical was given but no physical interfaces were found"
+            ));
+        }
+        physical
+    } else if let Some(names) = flags.interface_name {
+        for name in &names {
+            interfaces::validate(name, &discovered)?;
+        }
+        names
+    } else if !config.profile.is_empty() {
+        let names: Vec<String> = config
+            .profile
+            .iter()
+            .map(|profile| profile.interface.clone())
+            .collect();
+        for name in &names {
+            interfaces::validate(name, &discovered)?;
+        }
+        names
+    } else {
+        let default = interfaces::default_interface(&discovered).ok_or_else(|| {
+            anyhow!(
+                "no --interface-name given and no up, non-loopback interface with a \
+                link-layer address was found to default to"
+            )
+        })?;
+        vec![default.name.clone()]
+    };
+
+    let interfaces = interface_names
+        .into_iter()
+        .map(|interface_name| {
+            let profile = config.profile_for(&interface_name);
+            let label = config.label_for(&interface_name).to_owned();
+            let cycle_seconds = flags
+                .cycle_seconds
+                .or_else(|| profile.and_then(|profile| profile.cycle_seconds))
+                .or(config.cycle_seconds)
+                .unwrap_or(DEFAULT_CYCLE_SECONDS);
+            let variance = profile
+                .and_then(|profile| profile.variance)
+                .unwrap_or(CYCLE_VARIANCE);
+            let allowed_vendors = profile.and_then(config::Profile::allowed_vendors);
+            let nickname = profile.and_then(|profile| profile.nickname.clone());
+
+            scheduler::InterfaceSchedule {
+                interface_name,
+                label,
+                cycle_seconds,
+                variance,
+                allowed_vendors,
+                nickname,
+            }
+        })
+        .collect();
+
+    scheduler::run(
         &mut rng,
         &mut vendor_picker,
-        &flags.interface_name,
-        flags.cycle_seconds,
+        interfaces,
+        name_words,
+        use_command,
         flags.dry_run,
+        flags.locally_administered,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_mac_address_bytes_rejects_non_hex_octets() {
+        assert!(parse_mac_address_bytes("not-a:mac:address:at:all:here").is_err());
+    }
+
+    #[test]
+    fn from_display_name_is_case_insensitive() {
+        assert_eq!(Vendor::from_display_name("intel"), Some(Vendor::Intel));
+        assert_eq!(Vendor::from_display_name("AMD"), Some(Vendor::Amd));
+        assert_eq!(Vendor::from_display_name("HP"), Some(Vendor::HewlettPackard));
+    }
+
+    #[test]
+    fn from_display_name_rejects_unknown_vendors() {
+        assert_eq!(Vendor::from_display_name("bogus"), None);
+    }
+
+    #[test]
+    fn format_and_parse_mac_address_round_trip() {
+        let bytes = [0x00, 0x1b, 0x77, 0xab, 0xcd, 0xef];
+        let formatted = format_mac_address(&bytes);
+
+        assert_eq!(formatted, "00:1b:77:ab:cd:ef");
+        assert_eq!(parse_mac_address_bytes(&formatted).unwrap(), bytes);
+    }
+
+    #[test]
+    fn new_random_mac_address_is_always_unicast() {
+        let mut rng = thread_rng();
+        let mut picker_rng = thread_rng();
+        let mut picker = VendorPicker::new(&mut picker_rng);
+
+        for _ in 0..100 {
+            let (_, address, _) =
+                new_random_mac_address(&mut rng, &mut picker, None, 0, None, false);
+            let bytes = parse_mac_address_bytes(&address).unwrap();
+            assert_eq!(bytes[0] & MULTICAST_BIT, 0, "{address} is multicast");
+        }
+    }
+
+    #[test]
+    fn new_random_mac_address_sets_the_locally_administered_bit_when_requested() {
+        let mut rng = thread_rng();
+        let mut picker_rng = thread_rng();
+        let mut picker = VendorPicker::new(&mut picker_rng);
+
+        for _ in 0..100 {
+            let (vendor, address, _) =
+                new_random_mac_address(&mut rng, &mut picker, None, 0, None, true);
+            let bytes = parse_mac_address_bytes(&address).unwrap();
+
+            assert!(vendor.is_none());
+            assert_eq!(bytes[0] & LOCALLY_ADMINISTERED_BIT, LOCALLY_ADMINISTERED_BIT);
+        }
+    }
+}
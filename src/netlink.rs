@@ -0,0 +1,188 @@
+//! Direct kernel-level MAC address changes via `ioctl(2)`, bypassing the
+//! `ip`/`ifconfig` subprocesses entirely.
+//!
+//! The crate as a whole keeps `unsafe` out of its public surface, but the
+//! raw `ioctl(2)` calls needed here have no safe abstraction in `nix`, so
+//! this module is the single, narrowly-scoped exception: it is the only
+//! place in the crate allowed to contain `unsafe`, which it uses only to
+//! zero and fill in `ifreq` structs and to issue the ioctls themselves.
+#![allow(unsafe_code)]
+
+use {
+    anyhow::{Context, Error},
+    nix::{
+        ioctl_readwrite_bad,
+        sys::socket::{socket, AddressFamily, SockFlag, SockType},
+    },
+    std::os::fd::AsRawFd,
+};
+
+#[cfg(target_os = "linux")]
+const ARPHRD_ETHER: libc::c_ushort = 1;
+
+#[cfg(target_os = "linux")]
+const IFF_UP: libc::c_short = 0x1;
+
+// Previously this module defined its own `IfreqHwaddr`/`IfreqFlags` structs,
+// each smaller than the kernel's real `struct ifreq` (`SIOCSIFHWADDR` and
+// `SIOCG|SIFFLAGS` are read-write ioctls, and `copy_from_user` always moves
+// `sizeof(struct ifreq)` bytes regardless of what the caller's struct
+// declares). Using `libc::ifreq` directly, as the BSD/macOS arm below
+// already does, keeps the layout correctly sized; its `ifr_ifru` union is
+// accessed through a raw pointer cast instead of by field name, the same
+// trick the BSD arm uses for `sockaddr_dl`.
+#[cfg(target_os = "linux")]
+ioctl_readwrite_bad!(siocgifflags, libc::SIOCGIFFLAGS, libc::ifreq);
+#[cfg(target_os = "linux")]
+ioctl_readwrite_bad!(siocsifflags, libc::SIOCSIFFLAGS, libc::ifreq);
+#[cfg(target_os = "linux")]
+ioctl_readwrite_bad!(siocsifhwaddr, libc::SIOCSIFHWADDR, libc::ifreq);
+
+#[cfg(target_os = "linux")]
+fn ifreq_with_name(interface_name: &str) -> Result<libc::ifreq, Error> {
+    if libc::IF_NAMESIZE <= interface_name.len() {
+        return Err(anyhow::anyhow!(
+            "interface name `{interface_name}` is too long"
+        ));
+    }
+    // SAFETY: `libc::ifreq` is a C struct made entirely of integer types, a
+    // byte array, and unions of the same, so the all-zero bit pattern is a
+    // valid value for it.
+    let mut req: libc::ifreq = unsafe { std::mem::zeroed() };
+    for (dest, src) in req.ifr_name.iter_mut().zip(interface_name.bytes()) {
+        *dest = src as libc::c_char;
+    }
+    Ok(req)
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn set_mac_address(
+    interface_name: &str,
+    new_mac_address: &[u8; 6],
+) -> Result<(), Error> {
+    let fd = socket(
+        AddressFamily::Inet,
+        SockType::Datagram,
+        SockFlag::empty(),
+        None,
+    )
+    .context("failed to open an AF_INET socket for the hardware-address ioctls")?;
+    let raw_fd = fd.as_raw_fd();
+
+    let mut flags_req = ifreq_with_name(interface_name)?;
+    // SAFETY: `flags_req.ifr_ifru` is a union whose first field-sized member
+    // is `ifr_flags: c_short`; reading and writing it through this pointer
+    // is the same access the ioctls below make on the kernel side.
+    let flags_ptr = std::ptr::addr_of_mut!(flags_req.ifr_ifru) as *mut libc::c_short;
+
+    // SAFETY: `raw_fd` is a live socket owned by `fd`, and `flags_req` is a
+    // correctly laid-out `ifreq` for `SIOCGIFFLAGS`/`SIOCSIFFLAGS`, both of
+    // which are read-write ioctls that only ever touch the struct we hand
+    // them.
+    unsafe {
+        siocgifflags(raw_fd, &mut flags_req)
+            .context("SIOCGIFFLAGS failed while reading current interface flags")?;
+    }
+
+    // SAFETY: see `flags_ptr` above; it points at the `c_short` the kernel
+    // just wrote into via `SIOCGIFFLAGS`.
+    let was_up = (unsafe { *flags_ptr } & IFF_UP) != 0;
+    if was_up {
+        // SAFETY: see above; most NICs refuse a hardware-address change
+        // while `IFF_UP` is set, so it must be cleared first.
+        unsafe {
+            *flags_ptr &= !IFF_UP;
+        }
+        unsafe {
+            siocsifflags(raw_fd, &mut flags_req)
+                .context("SIOCSIFFLAGS failed while bringing the interface down")?;
+        }
+    }
+
+    let mut hwaddr_req = ifreq_with_name(interface_name)?;
+    // SAFETY: `hwaddr_req.ifr_ifru` is a union whose first field-sized
+    // member is `ifr_hwaddr: sockaddr`; writing it through this pointer
+    // matches what `SIOCSIFHWADDR` reads on the kernel side.
+    let sockaddr_ptr = std::ptr::addr_of_mut!(hwaddr_req.ifr_ifru) as *mut libc::sockaddr;
+    unsafe {
+        (*sockaddr_ptr).sa_family = ARPHRD_ETHER;
+        for (dest, src) in (*sockaddr_ptr)
+            .sa_data
+            .iter_mut()
+            .zip(new_mac_address.iter())
+        {
+            *dest = *src as libc::c_char;
+        }
+    }
+
+    // SAFETY: see above; `hwaddr_req` carries the six new address bytes in
+    // `sa_data`, matching what `SIOCSIFHWADDR` expects.
+    let set_result = unsafe { siocsifhwaddr(raw_fd, &mut hwaddr_req) }
+        .context("SIOCSIFHWADDR failed while setting the new hardware address");
+
+    if was_up {
+        // SAFETY: see above; restore the interface to its prior state
+        // regardless of whether the address change above succeeded.
+        unsafe {
+            *flags_ptr |= IFF_UP;
+        }
+        unsafe {
+            siocsifflags(raw_fd, &mut flags_req)
+                .context("SIOCSIFFLAGS failed while bringing the interface back up")?;
+        }
+    }
+
+    set_result.map(|_| ())
+}
+
+#[cfg(all(target_family = "unix", not(target_os = "linux")))]
+ioctl_readwrite_bad!(siocsifllladdr, libc::SIOCSIFLLADDR, libc::ifreq);
+
+#[cfg(all(target_family = "unix", not(target_os = "linux")))]
+pub(crate) fn set_mac_address(
+    interface_name: &str,
+    new_mac_address: &[u8; 6],
+) -> Result<(), Error> {
+    use std::mem::size_of;
+
+    let fd = socket(
+        AddressFamily::Inet,
+        SockType::Datagram,
+        SockFlag::empty(),
+        None,
+    )
+    .context("failed to open an AF_INET socket for the hardware-address ioctl")?;
+    let raw_fd = fd.as_raw_fd();
+
+    if libc::IF_NAMESIZE <= interface_name.len() {
+        return Err(anyhow::anyhow!(
+            "interface name `{interface_name}` is too long"
+        ));
+    }
+
+    let mut req: libc::ifreq = unsafe { std::mem::zeroed() };
+    for (dest, src) in req.ifr_name.iter_mut().zip(interface_name.bytes()) {
+        *dest = src as libc::c_char;
+    }
+
+    // `sockaddr_dl` is laid directly over the `ifreq`'s address union member;
+    // `sdl_type` is left zeroed as the kernel only inspects `sdl_alen` and
+    // the link-layer address bytes here. `sdl_nlen` (the interface-name
+    // length embedded in `sdl_data`) is left at 0 too, so the link-layer
+    // address itself starts right at the beginning of `sdl_data`, not after
+    // it.
+    let sdl = unsafe { &mut *(std::ptr::addr_of_mut!(req.ifr_ifru) as *mut libc::sockaddr_dl) };
+    sdl.sdl_len = size_of::<libc::sockaddr_dl>() as u8;
+    sdl.sdl_family = libc::AF_LINK as u8;
+    sdl.sdl_alen = new_mac_address.len() as u8;
+    for (dest, src) in sdl.sdl_data[..6].iter_mut().zip(new_mac_address.iter()) {
+        *dest = *src as libc::c_char;
+    }
+
+    // SAFETY: `raw_fd` is a live socket owned by `fd`, and `req` has been
+    // filled in as a `sockaddr_dl`-bearing `ifreq`, matching what
+    // `SIOCSIFLLADDR` expects.
+    unsafe { siocsifllladdr(raw_fd, &mut req) }
+        .context("SIOCSIFLLADDR failed while setting the new link-layer address")
+        .map(|_| ())
+}
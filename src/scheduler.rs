@@ -0,0 +1,251 @@
+//! Concurrently rotates several interfaces, each on its own independent
+//! cycle.
+//!
+//! On Linux, one `timerfd` per interface is multiplexed through a single
+//! `epoll` instance, keeping the process asleep in the kernel between
+//! rotations rather than serializing every interface behind one thread's
+//! blocking `sleep`. `epoll`/`timerfd` are Linux-only, so macOS/BSD instead
+//! get a thread per interface, each blocking on `std::thread::sleep`
+//! between its own rotations.
+
+use crate::Vendor;
+
+/// One interface's schedule, after CLI flags and config-file profiles have
+/// already been merged by `main`.
+pub(crate) struct InterfaceSchedule {
+    pub(crate) interface_name: String,
+    pub(crate) label: String,
+    pub(crate) cycle_seconds: usize,
+    pub(crate) variance: f64,
+    pub(crate) allowed_vendors: Option<Vec<Vendor>>,
+    pub(crate) nickname: Option<String>,
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::InterfaceSchedule;
+    use crate::{rotate_once, variate, RotationSpec, VendorPicker, MAX_AMOUNT_OF_ERRORS};
+    use anyhow::{Context, Error};
+    use nix::sys::{
+        epoll::{Epoll, EpollCreateFlags, EpollEvent, EpollFlags, EpollTimeout},
+        time::TimeSpec,
+        timerfd::{ClockId, Expiration, TimerFd, TimerFlags, TimerSetTimeFlags},
+    };
+    use rand::prelude::ThreadRng;
+    use std::time::Duration;
+
+    struct InterfaceState {
+        interface_name: String,
+        label: String,
+        cycle_seconds: usize,
+        variance: f64,
+        allowed_vendors: Option<Vec<crate::Vendor>>,
+        nickname: Option<String>,
+        timer: TimerFd,
+        errors: Vec<Error>,
+    }
+
+    fn arm(
+        rng: &mut ThreadRng,
+        timer: &TimerFd,
+        cycle_seconds: usize,
+        variance: f64,
+    ) -> Result<(), Error> {
+        let variation = variate(rng, cycle_seconds, variance);
+        let duration = Duration::from_millis((variation.round() * 1000.0) as u64);
+        timer
+            .set(
+                Expiration::OneShot(TimeSpec::from_duration(duration)),
+                TimerSetTimeFlags::empty(),
+            )
+            .context("failed to arm an interface's rotation timer")
+    }
+
+    /// Drives every interface's rotation loop concurrently by multiplexing
+    /// one `timerfd` per interface through a single `epoll` instance. Each
+    /// interface keeps its own sequential-error budget and drops out
+    /// independently once it is exhausted; this only returns once every
+    /// interface has failed out, collating all of their final errors.
+    pub(crate) fn run(
+        rng: &mut ThreadRng,
+        picker: &mut VendorPicker,
+        interfaces: Vec<InterfaceSchedule>,
+        name_words: usize,
+        use_command: bool,
+        dry_run: bool,
+        locally_administered: bool,
+    ) -> Result<(), Error> {
+        let epoll =
+            Epoll::new(EpollCreateFlags::empty()).context("failed to create an epoll instance")?;
+
+        let mut states: Vec<Option<InterfaceState>> = Vec::with_capacity(interfaces.len());
+        for schedule in interfaces {
+            let timer = TimerFd::new(ClockId::CLOCK_MONOTONIC, TimerFlags::empty())
+                .context("failed to create a timerfd for an interface")?;
+            arm(rng, &timer, schedule.cycle_seconds, schedule.variance)?;
+
+            let token = states.len() as u64;
+            epoll
+                .add(&timer, EpollEvent::new(EpollFlags::EPOLLIN, token))
+                .context("failed to register an interface's timer with epoll")?;
+
+            states.push(Some(InterfaceState {
+                interface_name: schedule.interface_name,
+                label: schedule.label,
+                cycle_seconds: schedule.cycle_seconds,
+                variance: schedule.variance,
+                allowed_vendors: schedule.allowed_vendors,
+                nickname: schedule.nickname,
+                timer,
+                errors: Vec::with_capacity(MAX_AMOUNT_OF_ERRORS),
+            }));
+        }
+
+        let mut active = states.len();
+        let mut final_errors: Vec<Error> = Vec::new();
+        let mut events = vec![EpollEvent::empty(); states.len().max(1)];
+
+        while 0 < active {
+            let ready = epoll
+                .wait(&mut events, EpollTimeout::NONE)
+                .context("epoll_wait failed while waiting for the next rotation")?;
+
+            for event in &events[..ready] {
+                let token = event.data() as usize;
+                let Some(state) = states[token].as_mut() else {
+                    continue;
+                };
+
+                state
+                    .timer
+                    .wait()
+                    .context("failed to read an interface timer's expiration count")?;
+
+                let interface_name = state.interface_name.clone();
+                let label = state.label.clone();
+                let spec = RotationSpec {
+                    allowed_vendors: state.allowed_vendors.as_deref(),
+                    name_words,
+                    fixed_nickname: state.nickname.as_deref(),
+                    use_command,
+                    dry_run,
+                    locally_administered,
+                };
+
+                match rotate_once(
+                    rng,
+                    picker,
+                    &interface_name,
+                    &label,
+                    &spec,
+                    &mut state.errors,
+                ) {
+                    Ok(()) => arm(rng, &state.timer, state.cycle_seconds, state.variance)?,
+                    Err(err) => {
+                        epoll
+                            .delete(&state.timer)
+                            .context("failed to deregister a failed interface's timer")?;
+                        final_errors.push(err);
+                        states[token] = None;
+                        active -= 1;
+                    }
+                }
+            }
+        }
+
+        if final_errors.is_empty() {
+            Ok(())
+        } else {
+            Err(crate::collate_errors(final_errors))
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod thread_per_interface {
+    use super::InterfaceSchedule;
+    use crate::{rotate_once, variate, RotationSpec, VendorPicker, MAX_AMOUNT_OF_ERRORS};
+    use anyhow::Error;
+    use rand::prelude::ThreadRng;
+    use std::{sync::mpsc, thread, time::Duration};
+
+    /// Drives every interface's rotation loop concurrently, one OS thread
+    /// per interface, each blocking on `std::thread::sleep` between
+    /// rotations. `ThreadRng` is thread-local and so cannot cross into the
+    /// spawned threads, so `rng`/`picker` go unused here; they stay in the
+    /// signature to match the Linux backend's, which `main` calls without
+    /// caring which backend it got. Each interface keeps its own
+    /// sequential-error budget and drops out independently once it is
+    /// exhausted; this only returns once every interface has failed out,
+    /// collating all of their final errors.
+    pub(crate) fn run(
+        _rng: &mut ThreadRng,
+        _picker: &mut VendorPicker,
+        interfaces: Vec<InterfaceSchedule>,
+        name_words: usize,
+        use_command: bool,
+        dry_run: bool,
+        locally_administered: bool,
+    ) -> Result<(), Error> {
+        let (error_sender, error_receiver) = mpsc::channel();
+
+        let handles: Vec<_> = interfaces
+            .into_iter()
+            .map(|schedule| {
+                let error_sender = error_sender.clone();
+                thread::spawn(move || {
+                    let mut rng = rand::thread_rng();
+                    let mut picker_rng = rand::thread_rng();
+                    let mut picker = VendorPicker::new(&mut picker_rng);
+                    let mut errors = Vec::with_capacity(MAX_AMOUNT_OF_ERRORS);
+
+                    loop {
+                        let variation = variate(&mut rng, schedule.cycle_seconds, schedule.variance);
+                        let duration = Duration::from_millis((variation.round() * 1000.0) as u64);
+                        thread::sleep(duration);
+
+                        let spec = RotationSpec {
+                            allowed_vendors: schedule.allowed_vendors.as_deref(),
+                            name_words,
+                            fixed_nickname: schedule.nickname.as_deref(),
+                            use_command,
+                            dry_run,
+                            locally_administered,
+                        };
+
+                        if let Err(err) = rotate_once(
+                            &mut rng,
+                            &mut picker,
+                            &schedule.interface_name,
+                            &schedule.label,
+                            &spec,
+                            &mut errors,
+                        ) {
+                            let _ = error_sender.send(err);
+                            break;
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        drop(error_sender);
+        let final_errors: Vec<Error> = error_receiver.into_iter().collect();
+
+        for handle in handles {
+            let _ = handle.join();
+        }
+
+        if final_errors.is_empty() {
+            Ok(())
+        } else {
+            Err(crate::collate_errors(final_errors))
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) use linux::run;
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) use thread_per_interface::run;